@@ -1,5 +1,9 @@
 use clap::Parser;
-use std::{cmp::max, time::Instant};
+use std::{
+    cmp::max,
+    collections::{HashMap, HashSet},
+    time::Instant,
+};
 
 /// Program to find the max subsequence of two strings
 /// Based on the following paper: https://ioi.di.unimi.it/maxsubseq.pdf
@@ -14,6 +18,12 @@ struct Args {
     #[arg(long)]
     s2: String,
 
+    /// An additional string to align, for `lcs_kdim`. Pass multiple times to align more than
+    /// two sequences at once, e.g. `--string abc --string def`; combined with `s1`/`s2` to form
+    /// the full ordered list of inputs
+    #[arg(long = "string")]
+    strings: Vec<String>,
+
     /// Whether to benchmark the program
     #[arg(short, long)]
     benchmark: bool,
@@ -25,8 +35,30 @@ struct Args {
     /// - lcs_dynamic: Dynamic Programming
     ///
     /// - lcs_rec: Recursive
+    ///
+    /// - lcs_hunt: Hunt–Szymanski matchlist algorithm, fast on sparse matches
+    ///
+    /// - lcs_hirschberg: Hirschberg's divide-and-conquer algorithm, linear space
+    ///
+    /// - lcs_kdim: Generalizes lcs_dynamic to the common subsequence of K strings
     #[arg(short, long, default_value = "lcs_for")]
     algorithm: String,
+
+    /// Whether to reconstruct and print the actual LCS string, not just its length
+    ///
+    /// lcs_kdim and lcs_hirschberg reconstruct it themselves, everything else backtracks
+    /// through the `lcs_dynamic` DP matrix
+    #[arg(long)]
+    show_subsequence: bool,
+
+    /// Whether to enumerate every distinct longest common subsequence, not just one
+    #[arg(long)]
+    all: bool,
+
+    /// Caps the number of distinct subsequences collected by `--all`, to guard against
+    /// combinatorial blowup on inputs with many ties
+    #[arg(long)]
+    max_results: Option<usize>,
 }
 
 fn benchmark<F>(func: F, benchmark: bool) -> Option<std::time::Duration>
@@ -48,17 +80,32 @@ fn main() {
     println!("s1: {}", args.s1);
     println!("s2: {}", args.s2);
 
+    // chars, not bytes, so we get real O(1) indexing and don't mangle multi-byte characters
+    let x: Vec<char> = args.s1.chars().collect();
+    let y: Vec<char> = args.s2.chars().collect();
+
+    // s1/s2 plus any extra --string args, for lcs_kdim
+    let mut kdim_inputs = vec![x.clone(), y.clone()];
+    kdim_inputs.extend(args.strings.iter().map(|s| s.chars().collect::<Vec<char>>()));
+
+    let mut hirschberg_subsequence: Option<String> = None;
+
     let duration = benchmark(
         || {
             let lcs = match args.algorithm.as_str() {
-                "lcs_for" => lcs_for(&args.s1, &args.s2),
-                "lcs_dynamic" => lcs_dynamic(&args.s1, &args.s2),
-                "lcs_rec" => lcs_rec(
-                    &args.s1,
-                    &args.s2,
-                    args.s1.len() as i32 - 1,
-                    args.s2.len() as i32 - 1,
-                ),
+                "lcs_for" => lcs_for(&x, &y),
+                "lcs_dynamic" => lcs_dynamic(&x, &y),
+                "lcs_rec" => lcs_rec(&x, &y, x.len() as i32 - 1, y.len() as i32 - 1),
+                "lcs_hunt" => lcs_hunt(&x, &y),
+                "lcs_hirschberg" => {
+                    let subsequence = lcs_hirschberg(&x, &y);
+                    let len = subsequence.chars().count() as i32;
+
+                    hirschberg_subsequence = Some(subsequence);
+
+                    len
+                }
+                "lcs_kdim" => lcs_kdim(&kdim_inputs),
                 _ => panic!("Invalid algorithm"),
             };
 
@@ -70,13 +117,39 @@ fn main() {
     if let Some(duration) = duration {
         println!("Time elapsed: {:?}", duration);
     }
+
+    if args.show_subsequence {
+        let subsequence = match args.algorithm.as_str() {
+            "lcs_kdim" => lcs_kdim_subsequence(&kdim_inputs),
+            // reuse what we already built above if we have it, otherwise get one the same way -
+            // never fall back to the full matrix, that's the whole point of hirschberg
+            "lcs_hirschberg" => hirschberg_subsequence.unwrap_or_else(|| lcs_hirschberg(&x, &y)),
+            _ => {
+                let dp = lcs_dynamic_matrix(&x, &y);
+
+                backtrack_lcs(&dp, &x, &y)
+            }
+        };
+
+        println!("subsequence: {}", subsequence);
+    }
+
+    if args.all {
+        let all_lcs = lcs_all(&x, &y, args.max_results);
+
+        println!("distinct LCS count: {}", all_lcs.len());
+
+        for subsequence in &all_lcs {
+            println!("{}", subsequence);
+        }
+    }
 }
 
 /// Finds the max subsequence of two strings using a for loop, a vector of already counted characters and
 /// the position of the last character that was counted (to make sure that the sequence is valid)
 ///
 /// Kinda the most naive approach, first thing that came to my mind
-fn lcs_for(x: &String, y: &String) -> i32 {
+fn lcs_for(x: &[char], y: &[char]) -> i32 {
     let mut lcs = 0;
 
     let mut already_counted_chars = vec![];
@@ -86,14 +159,14 @@ fn lcs_for(x: &String, y: &String) -> i32 {
     let mut max_char_pos = 0;
 
     for i in 0..x.len() {
-        let x_char = x.chars().nth(i).unwrap();
+        let x_char = x[i];
 
         if already_counted_chars.contains(&x_char) {
             continue;
         }
 
         for j in 0..y.len() {
-            let y_char = y.chars().nth(j).unwrap();
+            let y_char = y[j];
 
             if already_counted_chars.contains(&y_char) {
                 continue;
@@ -114,27 +187,247 @@ fn lcs_for(x: &String, y: &String) -> i32 {
 /// Finds the max subsequence of two strings using recursion
 ///
 /// The most simple of them all
-fn lcs_rec(x: &String, y: &String, i: i32, j: i32) -> i32 {
+fn lcs_rec(x: &[char], y: &[char], i: i32, j: i32) -> i32 {
     if i == -1 || j == -1 {
         return 0;
     }
 
-    if x.chars().nth(i as usize).unwrap() == y.chars().nth(j as usize).unwrap() {
+    if x[i as usize] == y[j as usize] {
         return 1 + lcs_rec(x, y, i - 1, j - 1);
     } else {
         return max(lcs_rec(x, y, i - 1, j), lcs_rec(x, y, i, j - 1));
     }
 }
 
+/// Finds the max subsequence of two strings using the Hunt–Szymanski matchlist algorithm
+///
+/// Good when matches are sparse, runs in O((r + n) log n) where r is the number of matches
+fn lcs_hunt(x: &[char], y: &[char]) -> i32 {
+    // positions each char occurs at in y, descending - keeps a single x char from extending more
+    // than one thresh entry per pass
+    let mut positions_in_y: HashMap<char, Vec<usize>> = HashMap::new();
+
+    for (j, &y_char) in y.iter().enumerate() {
+        positions_in_y.entry(y_char).or_insert_with(Vec::new).push(j);
+    }
+
+    for positions in positions_in_y.values_mut() {
+        positions.reverse();
+    }
+
+    // thresh[k] is the smallest column index at which a common subsequence of length k + 1 can end
+    let mut thresh: Vec<usize> = vec![];
+
+    for &x_char in x {
+        let Some(match_positions) = positions_in_y.get(&x_char) else {
+            continue;
+        };
+
+        for &j in match_positions {
+            match thresh.binary_search(&j) {
+                Ok(pos) => thresh[pos] = j,
+                Err(pos) if pos == thresh.len() => thresh.push(j),
+                Err(pos) => thresh[pos] = j,
+            }
+        }
+    }
+
+    return thresh.len() as i32;
+}
+
+/// Finds the LCS string of two strings using Hirschberg's divide-and-conquer algorithm
+///
+/// Same O(m*n) time as `lcs_dynamic` but only O(min(m, n)) space - no full matrix, just a
+/// rolling row at a time
+fn lcs_hirschberg(x: &[char], y: &[char]) -> String {
+    // split whichever string is longer, size the rolling rows by the shorter one - keeps the
+    // space bound regardless of which of s1/s2 is longer
+    if x.len() >= y.len() {
+        return hirschberg_rec(x, y);
+    } else {
+        return hirschberg_rec(y, x);
+    }
+}
+
+/// Recursive half of `lcs_hirschberg`: splits x at its midpoint, uses a forward and a backward
+/// rolling-row DP to find the matching split point in y, then recurses on the two halves
+fn hirschberg_rec(x: &[char], y: &[char]) -> String {
+    if x.is_empty() {
+        return String::new();
+    }
+
+    if x.len() == 1 {
+        return if y.contains(&x[0]) {
+            x[0].to_string()
+        } else {
+            String::new()
+        };
+    }
+
+    let mid = x.len() / 2;
+    let (x1, x2) = x.split_at(mid);
+
+    let forward = lcs_dp_row(x1.iter(), y);
+
+    // x2 is walked backwards in place, a reversed copy would be sized by the longer string
+    let y_rev: Vec<char> = y.iter().rev().cloned().collect();
+    let backward_rev = lcs_dp_row(x2.iter().rev(), &y_rev);
+
+    let mut split = 0;
+    let mut best = -1;
+
+    for k in 0..=y.len() {
+        let score = forward[k] + backward_rev[y.len() - k];
+
+        if score > best {
+            best = score;
+            split = k;
+        }
+    }
+
+    // free this level's rows before recursing, so only one level's rows are alive at a time
+    drop((forward, backward_rev, y_rev));
+
+    let left = hirschberg_rec(x1, &y[..split]);
+    let right = hirschberg_rec(x2, &y[split..]);
+
+    return left + &right;
+}
+
+/// Computes only the last row of the `lcs_dynamic` DP matrix for x against y, using two rolling
+/// rows instead of the full (m+1)x(n+1) matrix
+fn lcs_dp_row<'a>(x: impl Iterator<Item = &'a char>, y: &[char]) -> Vec<i32> {
+    let mut prev = vec![0; y.len() + 1];
+    let mut curr = vec![0; y.len() + 1];
+
+    for &x_char in x {
+        for j in 1..=y.len() {
+            if x_char == y[j - 1] {
+                curr[j] = prev[j - 1] + 1;
+            } else {
+                curr[j] = max(prev[j], curr[j - 1]);
+            }
+        }
+
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    return prev;
+}
+
+/// Generalizes `lcs_dynamic` to K strings - same idea, just a DP table addressed by an index
+/// vector instead of a fixed 2d matrix
+fn lcs_kdim(chars: &Vec<Vec<char>>) -> i32 {
+    let start: Vec<usize> = chars.iter().map(|c| c.len()).collect();
+    let mut memo: HashMap<Vec<usize>, i32> = HashMap::new();
+
+    return lcs_kdim_rec(chars, start, &mut memo);
+}
+
+/// Recursive half of `lcs_kdim`: when all K current characters match, takes the diagonal
+/// neighbour, otherwise takes the max over the K neighbours that each decrement one dimension
+fn lcs_kdim_rec(chars: &Vec<Vec<char>>, indices: Vec<usize>, memo: &mut HashMap<Vec<usize>, i32>) -> i32 {
+    if indices.iter().any(|&i| i == 0) {
+        return 0;
+    }
+
+    if let Some(&cached) = memo.get(&indices) {
+        return cached;
+    }
+
+    let first_char = chars[0][indices[0] - 1];
+    let all_match = (1..chars.len()).all(|k| chars[k][indices[k] - 1] == first_char);
+
+    let result = if all_match {
+        let mut diagonal = indices.clone();
+
+        for i in diagonal.iter_mut() {
+            *i -= 1;
+        }
+
+        1 + lcs_kdim_rec(chars, diagonal, memo)
+    } else {
+        let mut best = 0;
+
+        for k in 0..chars.len() {
+            let mut neighbour = indices.clone();
+            neighbour[k] -= 1;
+
+            best = max(best, lcs_kdim_rec(chars, neighbour, memo));
+        }
+
+        best
+    };
+
+    memo.insert(indices, result);
+
+    return result;
+}
+
+/// Same idea as `backtrack_lcs` but for K strings: fills in the memo via `lcs_kdim_rec`, then
+/// backtracks through it the same way
+fn lcs_kdim_subsequence(chars: &Vec<Vec<char>>) -> String {
+    let start: Vec<usize> = chars.iter().map(|c| c.len()).collect();
+    let mut memo: HashMap<Vec<usize>, i32> = HashMap::new();
+
+    lcs_kdim_rec(chars, start.clone(), &mut memo);
+
+    return backtrack_kdim(chars, &memo, start);
+}
+
+/// Recursive half of `lcs_kdim_subsequence`
+fn backtrack_kdim(chars: &Vec<Vec<char>>, memo: &HashMap<Vec<usize>, i32>, indices: Vec<usize>) -> String {
+    if indices.iter().any(|&i| i == 0) {
+        return String::new();
+    }
+
+    let first_char = chars[0][indices[0] - 1];
+    let all_match = (1..chars.len()).all(|k| chars[k][indices[k] - 1] == first_char);
+
+    if all_match {
+        let mut diagonal = indices.clone();
+
+        for i in diagonal.iter_mut() {
+            *i -= 1;
+        }
+
+        let mut subsequence = backtrack_kdim(chars, memo, diagonal);
+        subsequence.push(first_char);
+
+        return subsequence;
+    }
+
+    let current = memo[&indices];
+
+    for k in 0..chars.len() {
+        let mut neighbour = indices.clone();
+        neighbour[k] -= 1;
+
+        if memo.get(&neighbour) == Some(&current) {
+            return backtrack_kdim(chars, memo, neighbour);
+        }
+    }
+
+    return String::new();
+}
+
 /// Finds the max subsequence of two strings using dynamic programming and a 2d vector (matrix)
 ///
 /// The most complex of them all
-fn lcs_dynamic(x: &String, y: &String) -> i32 {
+fn lcs_dynamic(x: &[char], y: &[char]) -> i32 {
+    let dp = lcs_dynamic_matrix(x, y);
+
+    return dp[x.len()][y.len()];
+}
+
+/// Builds the DP matrix used by `lcs_dynamic`, exposed on its own so the matrix can also be
+/// backtracked to reconstruct the actual subsequence, not just its length
+fn lcs_dynamic_matrix(x: &[char], y: &[char]) -> Vec<Vec<i32>> {
     let mut dp = vec![vec![0; y.len() + 1]; x.len() + 1];
 
     for i in 1..=x.len() {
         for j in 1..=y.len() {
-            if x.chars().nth(i - 1).unwrap() == y.chars().nth(j - 1).unwrap() {
+            if x[i - 1] == y[j - 1] {
                 dp[i][j] = 1 + dp[i - 1][j - 1];
             } else {
                 dp[i][j] = max(dp[i - 1][j], dp[i][j - 1])
@@ -142,5 +435,100 @@ fn lcs_dynamic(x: &String, y: &String) -> i32 {
         }
     }
 
-    return dp[x.len()][y.len()];
+    return dp;
+}
+
+/// Enumerates every distinct longest common subsequence of x and y, walking the same DP matrix
+/// `backtrack_lcs` follows but branching into both neighbours whenever they tie for the maximum
+fn lcs_all(x: &[char], y: &[char], max_results: Option<usize>) -> HashSet<String> {
+    let dp = lcs_dynamic_matrix(x, y);
+    let mut memo: HashMap<(usize, usize), HashSet<String>> = HashMap::new();
+
+    return collect_all_lcs(&dp, x, y, x.len(), y.len(), max_results, &mut memo);
+}
+
+/// Recursive half of `lcs_all`, capping each collected set against `max_results` to guard
+/// against the combinatorial blowup of inputs with many tied subsequences. Memoized on `(i, j)`
+/// - otherwise the same cell gets revisited along exponentially many tied paths and the cap
+/// only trims the output, not the work
+fn collect_all_lcs(
+    dp: &Vec<Vec<i32>>,
+    x: &[char],
+    y: &[char],
+    i: usize,
+    j: usize,
+    max_results: Option<usize>,
+    memo: &mut HashMap<(usize, usize), HashSet<String>>,
+) -> HashSet<String> {
+    if let Some(cached) = memo.get(&(i, j)) {
+        return cached.clone();
+    }
+
+    let mut results = HashSet::new();
+
+    if i == 0 || j == 0 {
+        results.insert(String::new());
+        memo.insert((i, j), results.clone());
+
+        return results;
+    }
+
+    let x_char = x[i - 1];
+    let y_char = y[j - 1];
+
+    if x_char == y_char {
+        for prefix in collect_all_lcs(dp, x, y, i - 1, j - 1, max_results, memo) {
+            if let Some(cap) = max_results {
+                if results.len() >= cap {
+                    break;
+                }
+            }
+
+            results.insert(format!("{}{}", prefix, x_char));
+        }
+    } else {
+        if dp[i - 1][j] >= dp[i][j - 1] {
+            results.extend(collect_all_lcs(dp, x, y, i - 1, j, max_results, memo));
+        }
+
+        if dp[i][j - 1] >= dp[i - 1][j] {
+            results.extend(collect_all_lcs(dp, x, y, i, j - 1, max_results, memo));
+        }
+
+        if let Some(cap) = max_results {
+            while results.len() > cap {
+                let extra = results.iter().next().cloned().unwrap();
+                results.remove(&extra);
+            }
+        }
+    }
+
+    memo.insert((i, j), results.clone());
+
+    return results;
+}
+
+/// Reconstructs the actual LCS string by backtracking through a `lcs_dynamic_matrix` DP matrix,
+/// moving diagonally on a character match and otherwise stepping towards the larger neighbour
+fn backtrack_lcs(dp: &Vec<Vec<i32>>, x: &[char], y: &[char]) -> String {
+    let mut i = x.len();
+    let mut j = y.len();
+    let mut subsequence = String::new();
+
+    while i > 0 && j > 0 {
+        let x_char = x[i - 1];
+        let y_char = y[j - 1];
+
+        if x_char == y_char {
+            subsequence.insert(0, x_char);
+            i -= 1;
+            j -= 1;
+        } else if dp[i - 1][j] >= dp[i][j - 1] {
+            i -= 1;
+        } else {
+            j -= 1;
+        }
+    }
+
+    return subsequence;
 }